@@ -0,0 +1,90 @@
+//! A structured contact record.
+//!
+//! The CSV/vCard pipeline used to be pinned to exactly six flat fields, which
+//! cannot represent a contact with more than one phone or email, a postal
+//! address, or organization details. [`Contact`] holds all of that as typed,
+//! repeatable fields so no data has to be dropped on import or export.
+
+/// A contact's structured name.
+#[derive(Debug, Clone, Default)]
+pub struct Name {
+    pub first: String,
+    pub last: String,
+}
+
+/// The context a phone number, email address, or postal address applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ContactKind {
+    #[default]
+    Home,
+    Work,
+    Cell,
+    Fax,
+    Other(String),
+}
+
+impl ContactKind {
+    /// Returns the `TYPE=` value used in vCard 3.0/4.0 output: lower-case for
+    /// the known kinds, and the stored value as-is for [`ContactKind::Other`].
+    pub fn as_type_value(&self) -> &str {
+        match self {
+            ContactKind::Home => "home",
+            ContactKind::Work => "work",
+            ContactKind::Cell => "cell",
+            ContactKind::Fax => "fax",
+            ContactKind::Other(value) => value,
+        }
+    }
+}
+
+/// A single phone number with the context it applies to.
+#[derive(Debug, Clone)]
+pub struct Phone {
+    pub number: String,
+    pub kind: ContactKind,
+}
+
+/// A single email address with the context it applies to.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub address: String,
+    pub kind: ContactKind,
+}
+
+/// A postal address, stored as the individual `ADR` components from RFC 6350
+/// §6.3.1 (post office box, extended address, street, city, region, postal
+/// code, country).
+#[derive(Debug, Clone, Default)]
+pub struct Address {
+    pub po_box: String,
+    pub extended: String,
+    pub street: String,
+    pub city: String,
+    pub region: String,
+    pub postal_code: String,
+    pub country: String,
+    pub kind: ContactKind,
+}
+
+/// A contact ready to be serialized into a vCard, or parsed out of one.
+///
+/// Repeated properties (phones, emails, addresses, categories) are stored as
+/// `Vec`s rather than a single value, and the fields that a contact may simply
+/// not have (organization, title, birthday, URL, note) are `Option`s.
+#[derive(Debug, Clone, Default)]
+pub struct Contact {
+    pub name: Name,
+    pub phones: Vec<Phone>,
+    pub emails: Vec<Email>,
+    pub addresses: Vec<Address>,
+    pub org: Option<String>,
+    pub title: Option<String>,
+    pub bday: Option<String>,
+    pub url: Option<String>,
+    pub categories: Vec<String>,
+    pub note: Option<String>,
+    /// An external identifier for this contact, sourced from a CSV `ID`
+    /// column or a previously generated vCard's `UID` property. Used to
+    /// derive a stable vCard `UID` on (re-)export; see `resolve_uid`.
+    pub id: Option<String>,
+}
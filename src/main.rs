@@ -1,48 +1,335 @@
-use std::fmt::Write;
+mod contact;
+mod vcard_parser;
+
+use std::collections::HashMap;
+use std::fs;
 use std::fs::File;
 use std::io::{Read, Write as ioWrite};
 use std::path::{Path, PathBuf};
 use std::io;
+use std::sync::mpsc;
+use std::thread;
 
+use chrono::Utc;
+use csv::StringRecord;
 use eframe::{egui, Frame};
 use egui::Context;
+use uuid::Uuid;
+
+use contact::{Address, Contact, ContactKind, Email, Name, Phone};
 
-/// Creates a simple VCard (version 2.1) for a contact.
+/// Canonical vCard fields that can be driven from a CSV header, together with the
+/// header spellings accepted as aliases for each one.
+///
+/// Matching is case- and punctuation-insensitive (see [`normalize_header`]), so
+/// `"First Name"`, `"first_name"` and `"FIRSTNAME"` are all recognized as the same
+/// column. This lets the generator accept exports from Google, Outlook, Evolution
+/// and similar address books without the user having to rename columns first.
+/// A header may also carry a trailing number (`"EMAIL 2"`, `"EMAIL2"`) to supply a
+/// second value for the same field — see [`build_column_map`].
+const FIELD_ALIASES: &[(&str, &[&str])] = &[
+    ("first_name", &["FIRSTNAME", "FIRST_NAME", "FIRST NAME", "GIVEN", "GIVEN NAME", "GIVENNAME"]),
+    ("last_name", &["LASTNAME", "LAST_NAME", "LAST NAME", "SURNAME", "FAMILY NAME", "FAMILYNAME"]),
+    ("tel", &["TEL", "PHONE", "HOME PHONE", "TELEPHONE", "HOME"]),
+    ("mobile", &["MOBILE", "CELL", "MOBILE PHONE", "CELL PHONE"]),
+    ("email", &["EMAIL", "E-MAIL", "EMAIL ADDRESS"]),
+    ("address", &["ADDRESS", "STREET", "STREET ADDRESS"]),
+    ("org", &["ORG", "ORGANIZATION", "COMPANY"]),
+    ("title", &["TITLE", "JOB TITLE", "ROLE"]),
+    ("bday", &["BDAY", "BIRTHDAY", "DATE OF BIRTH"]),
+    ("url", &["URL", "WEBSITE", "HOMEPAGE"]),
+    ("categories", &["CATEGORIES", "CATEGORY", "TAGS", "GROUPS"]),
+    ("note", &["NOTE", "NOTES", "COMMENT", "COMMENTS"]),
+    ("id", &["ID", "UID", "CONTACT ID", "CONTACTID"]),
+];
+
+/// Normalizes a CSV header for alias comparison.
 ///
-/// This function generates a VCard as a `String` with the standard
-/// fields for name, phone numbers, email, and a note. The VCard
-/// can then be saved to a file or used as needed.
+/// Keeps only alphanumeric characters and upper-cases them, so that spacing,
+/// underscores, and case differences between e.g. `"First Name"` and `"FIRSTNAME"`
+/// do not prevent a match.
+fn normalize_header(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+/// Maps canonical vCard field names to the column indices that supply them in a
+/// CSV file.
+///
+/// Built once per file from the header row by [`build_column_map`], then used to
+/// look up each field for every data row regardless of the column order the file
+/// actually uses. A field may be backed by more than one column (e.g. `EMAIL` and
+/// `EMAIL 2`), in which case [`ColumnMap::get_all`] returns every non-empty value
+/// in header order.
+struct ColumnMap {
+    indices: HashMap<&'static str, Vec<usize>>,
+}
+
+impl ColumnMap {
+    /// Returns the value of `field`'s first matching column for `record`, or an
+    /// empty string if the field has no matching column or an empty cell.
+    fn get<'a>(&self, record: &'a StringRecord, field: &str) -> &'a str {
+        self.indices.get(field).and_then(|indices| indices.first()).and_then(|&index| record.get(index)).unwrap_or("")
+    }
+
+    /// Returns every non-empty value of `field` for `record`, across all of its
+    /// matching columns and with each cell further split on `;` to support a
+    /// semicolon-delimited list of values in a single column.
+    fn get_all(&self, record: &StringRecord, field: &str) -> Vec<String> {
+        let Some(indices) = self.indices.get(field) else {
+            return Vec::new();
+        };
+        indices
+            .iter()
+            .filter_map(|&index| record.get(index))
+            .flat_map(|value| value.split(';'))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+}
+
+/// Builds a [`ColumnMap`] from a CSV header row.
+///
+/// Each canonical field in [`FIELD_ALIASES`] is matched against the header columns,
+/// ignoring a trailing number in the header (so `"EMAIL"` and `"EMAIL 2"` both match
+/// the `email` field, contributing two separate columns to it). Fields with no
+/// matching column are simply absent from the map, and [`ColumnMap::get`]/
+/// [`ColumnMap::get_all`] will report them as empty.
+fn build_column_map(headers: &StringRecord) -> ColumnMap {
+    let mut indices: HashMap<&'static str, Vec<usize>> = HashMap::new();
+    for (index, header) in headers.iter().enumerate() {
+        let normalized = normalize_header(header);
+        let base = normalized.trim_end_matches(|c: char| c.is_ascii_digit());
+        for (field, aliases) in FIELD_ALIASES {
+            if aliases.iter().any(|alias| normalize_header(alias) == base) {
+                indices.entry(field).or_default().push(index);
+            }
+        }
+    }
+    ColumnMap { indices }
+}
+
+/// A vCard format revision supported for output.
+///
+/// Each version writes its own `VERSION:` value and uses its own parameter
+/// syntax for typed properties (see [`VCardVersion::uses_typed_parameters`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum VCardVersion {
+    #[default]
+    V2_1,
+    V3_0,
+    V4_0,
+}
+
+impl VCardVersion {
+    /// Returns the `VERSION:` property value for this revision.
+    fn as_str(self) -> &'static str {
+        match self {
+            VCardVersion::V2_1 => "2.1",
+            VCardVersion::V3_0 => "3.0",
+            VCardVersion::V4_0 => "4.0",
+        }
+    }
+
+    /// Whether `TEL`/`EMAIL` use the `TYPE=value,value` parameter syntax
+    /// introduced in 3.0, rather than the bare flag syntax of 2.1
+    /// (`TEL;HOME;VOICE:`).
+    fn uses_typed_parameters(self) -> bool {
+        !matches!(self, VCardVersion::V2_1)
+    }
+}
+
+/// How a batch of converted contacts is laid out on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum OutputMode {
+    /// All contacts concatenated into a single `.vcf` file.
+    #[default]
+    SingleFile,
+    /// A vdir: one `<uid>.vcf` file per contact in its own directory, as
+    /// expected by vdir-based address books (e.g. mates.rs, CardDAV clients).
+    Vdir,
+}
+
+impl OutputMode {
+    /// A short label for this mode, used in the UI dropdown.
+    fn as_str(self) -> &'static str {
+        match self {
+            OutputMode::SingleFile => "Single file",
+            OutputMode::Vdir => "Vdir (one file per contact)",
+        }
+    }
+}
+
+/// Escapes a vCard text value per RFC 6350 §3.4.
+///
+/// Backslashes, commas, and semicolons would otherwise be ambiguous with
+/// property/value delimiters, and a literal newline would break line folding,
+/// so all four are escaped. Each input character is examined once, so the
+/// backslashes added by an escape are never escaped again. A carriage return
+/// is dropped, so a CRLF line break becomes a single `\n`.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// Folds a logical vCard line so that no physical line exceeds 75 octets,
+/// per RFC 6350 §3.2.
+///
+/// Continuation lines are introduced with CRLF followed by a single space, and
+/// the fold points are chosen on `char` boundaries so a multibyte UTF-8
+/// sequence is never split across a fold.
+fn fold_line(line: &str) -> String {
+    const MAX_OCTETS: usize = 75;
+    if line.len() <= MAX_OCTETS {
+        return line.to_string();
+    }
+
+    let mut folded = String::new();
+    let mut segment_start = 0;
+    let mut budget = MAX_OCTETS;
+    for (index, ch) in line.char_indices() {
+        if index != segment_start && index - segment_start + ch.len_utf8() > budget {
+            folded.push_str(&line[segment_start..index]);
+            folded.push_str("\r\n ");
+            segment_start = index;
+            budget = MAX_OCTETS - 1; // continuation lines start with a folding space
+        }
+    }
+    folded.push_str(&line[segment_start..]);
+    folded
+}
+
+/// Appends a folded, CRLF-terminated property line to `vcard`.
+fn push_property(vcard: &mut String, line: &str) {
+    vcard.push_str(&fold_line(line));
+    vcard.push_str("\r\n");
+}
+
+/// Appends a contact's phone numbers as `TEL` properties.
+fn push_phones(vcard: &mut String, phones: &[Phone], version: VCardVersion) {
+    for phone in phones {
+        if version.uses_typed_parameters() {
+            push_property(vcard, &format!("TEL;TYPE={},voice:{}", phone.kind.as_type_value(), escape_text(&phone.number)));
+        } else {
+            let flags = match &phone.kind {
+                ContactKind::Work => "WORK;VOICE".to_string(),
+                ContactKind::Cell => "CELL;VOICE".to_string(),
+                ContactKind::Fax => "HOME;FAX".to_string(),
+                ContactKind::Other(value) => format!("{};VOICE", value.to_ascii_uppercase()),
+                ContactKind::Home => "HOME;VOICE".to_string(),
+            };
+            push_property(vcard, &format!("TEL;{flags}:{}", escape_text(&phone.number)));
+        }
+    }
+}
+
+/// Appends a contact's email addresses as `EMAIL` properties. The first email
+/// is marked preferred (`PREF`/`TYPE=pref,...`).
+fn push_emails(vcard: &mut String, emails: &[Email], version: VCardVersion) {
+    for (index, email) in emails.iter().enumerate() {
+        let is_preferred = index == 0;
+        if version.uses_typed_parameters() {
+            let pref = if is_preferred { "pref," } else { "" };
+            push_property(vcard, &format!("EMAIL;TYPE={pref}{},internet:{}", email.kind.as_type_value(), escape_text(&email.address)));
+        } else {
+            let kind_flag = if email.kind == ContactKind::Work { "WORK" } else { "HOME" };
+            let pref = if is_preferred { "PREF;" } else { "" };
+            push_property(vcard, &format!("EMAIL;{pref}{kind_flag};INTERNET:{}", escape_text(&email.address)));
+        }
+    }
+}
+
+/// Appends a contact's postal addresses as `ADR` properties, with components
+/// ordered per RFC 6350 §6.3.1: post office box, extended address, street,
+/// city, region, postal code, country.
+fn push_addresses(vcard: &mut String, addresses: &[Address], version: VCardVersion) {
+    for address in addresses {
+        let value = [
+            &address.po_box,
+            &address.extended,
+            &address.street,
+            &address.city,
+            &address.region,
+            &address.postal_code,
+            &address.country,
+        ]
+        .map(|component| escape_text(component))
+        .join(";");
+        if version.uses_typed_parameters() {
+            push_property(vcard, &format!("ADR;TYPE={}:{value}", address.kind.as_type_value()));
+        } else {
+            let kind_flag = if address.kind == ContactKind::Work { "WORK" } else { "HOME" };
+            push_property(vcard, &format!("ADR;{kind_flag}:{value}"));
+        }
+    }
+}
+
+/// Namespace used to derive a stable UID from a non-UUID external identifier,
+/// via UUID v5, so the same external id always derives the same vCard UID on
+/// every re-export.
+const UID_NAMESPACE: Uuid = Uuid::NAMESPACE_DNS;
+
+/// Resolves the `UID` to write for a contact, following the meli addressbook
+/// module's approach of keying contacts on a UUID.
+///
+/// * If `id` is already a valid UUID (e.g. one round-tripped from a
+///   previously generated vCard's `UID`), it is reused as-is.
+/// * Otherwise, if `id` is set (e.g. a CSV `ID` column), it is hashed into a
+///   stable UUID v5 so the same row always derives the same UID.
+/// * With no `id` at all, a random UUID v4 is generated.
+fn resolve_uid(id: Option<&str>) -> Uuid {
+    match id {
+        Some(id) => Uuid::parse_str(id).unwrap_or_else(|_| Uuid::new_v5(&UID_NAMESPACE, id.as_bytes())),
+        None => Uuid::new_v4(),
+    }
+}
+
+/// Creates a vCard for a [`Contact`] in the requested [`VCardVersion`], with
+/// the given `uid` as its `UID` property.
+///
+/// Phones, emails, and addresses are written as one property per entry, and
+/// categories as a single comma-separated `CATEGORIES` property; every text value is escaped and the resulting
+/// lines folded per RFC 6350 regardless of version. The caller supplies `uid`
+/// (typically from [`resolve_uid`]) rather than this function deriving one
+/// itself, so a single contact's `UID` property and vdir filename (see
+/// [`process_csv_with_progress`]) always agree. `REV` is set to the current
+/// RFC 3339 / ISO 8601 UTC timestamp, so address-book sync clients can dedupe
+/// contacts and detect updates across runs instead of importing a fresh
+/// duplicate every time. The vCard can then be saved to a file or used as
+/// needed.
 ///
 /// # Arguments
 ///
-/// * `first_name` - Contact's first name.
-/// * `last_name` - Contact's last name.
-/// * `tel` - Contact's home phone number.
-/// * `mobile` - Contact's mobile phone number.
-/// * `email` - Contact's email address.
-/// * `note` - Optional note about the contact.
+/// * `contact` - The contact to serialize.
+/// * `version` - The vCard revision to emit.
+/// * `uid` - The `UID` property value, shared with the contact's vdir filename.
 ///
 /// # Returns
 ///
-/// * `String` - A properly formatted VCard (version 2.1).
+/// * `String` - A properly formatted, CRLF-terminated vCard.
 ///
 /// # Panics
 ///
-/// This function **does not panic due to its own logic**. The only potential panics
-/// come from the `writeln!` macro, which unwraps formatting errors. Since the
-/// formatting strings are constant and safe, these panics should never occur.
+/// This function **does not panic**.
 ///
 /// # Examples
 ///
 /// ```
-/// let vcard = make_vcard(
-///     "Alice",
-///     "Smith",
-///     "123-456-7890",
-///     "098-765-4321",
-///     "alice@example.com",
-///     "Friend from school"
-/// );
+/// let mut contact = Contact::default();
+/// contact.name = Name { first: "Alice".into(), last: "Smith".into() };
+/// contact.emails.push(Email { address: "alice@example.com".into(), kind: ContactKind::Home });
+/// let uid = resolve_uid(contact.id.as_deref());
+/// let vcard = make_vcard(&contact, VCardVersion::V2_1, uid);
 /// println!("{}", vcard);
 /// ```
 ///
@@ -51,52 +338,171 @@ use egui::Context;
 /// ```text
 /// BEGIN:VCARD
 /// VERSION:2.1
-/// N:Smith;Alice
+/// N:Smith;Alice;;;
 /// FN:Alice Smith
-/// EMAIL;PREF;INTERNET:alice@example.com
-/// TEL;HOME;VOICE:123-456-7890
-/// TEL;HOME;VOICE:098-765-4321
-/// NOTE:Friend from school
-/// REV:1
+/// EMAIL;PREF;HOME;INTERNET:alice@example.com
+/// UID:2362c8ee-6b01-4a2c-9c2c-1f4f1a8f0b8e
+/// REV:20240518T121647Z
 /// END:VCARD
 /// ```
-fn make_vcard(first_name: &str, last_name: &str, tel: &str, mobile: &str, email: &str, note: &str) -> String {
+fn make_vcard(contact: &Contact, version: VCardVersion, uid: Uuid) -> String {
     let mut vcard = String::new();
-    writeln!(vcard, "BEGIN:VCARD").unwrap();
-    writeln!(vcard, "VERSION:2.1").unwrap();
-    writeln!(vcard, "N:{last_name};{first_name}").unwrap();
-    writeln!(vcard, "FN:{first_name} {last_name}").unwrap();
-    writeln!(vcard, "EMAIL;PREF;INTERNET:{email}").unwrap();
-    writeln!(vcard, "TEL;HOME;VOICE:{tel}").unwrap();
-    writeln!(vcard, "TEL;HOME;VOICE:{mobile}").unwrap();
-    writeln!(vcard, "NOTE:{note}").unwrap();
-    writeln!(vcard, "REV:1").unwrap();
-    writeln!(vcard, "END:VCARD").unwrap();
+    push_property(&mut vcard, "BEGIN:VCARD");
+    push_property(&mut vcard, &format!("VERSION:{}", version.as_str()));
+    push_property(&mut vcard, &format!("N:{};{};;;", escape_text(&contact.name.last), escape_text(&contact.name.first)));
+    let fn_value = format!("{} {}", escape_text(&contact.name.first), escape_text(&contact.name.last));
+    push_property(&mut vcard, &format!("FN:{}", fn_value.trim()));
+    if let Some(org) = &contact.org {
+        push_property(&mut vcard, &format!("ORG:{}", escape_text(org)));
+    }
+    if let Some(title) = &contact.title {
+        push_property(&mut vcard, &format!("TITLE:{}", escape_text(title)));
+    }
+    push_emails(&mut vcard, &contact.emails, version);
+    push_phones(&mut vcard, &contact.phones, version);
+    push_addresses(&mut vcard, &contact.addresses, version);
+    if let Some(bday) = &contact.bday {
+        push_property(&mut vcard, &format!("BDAY:{}", escape_text(bday)));
+    }
+    if let Some(url) = &contact.url {
+        push_property(&mut vcard, &format!("URL:{}", escape_text(url)));
+    }
+    if !contact.categories.is_empty() {
+        let joined = contact.categories.iter().map(|category| escape_text(category)).collect::<Vec<_>>().join(",");
+        push_property(&mut vcard, &format!("CATEGORIES:{joined}"));
+    }
+    if let Some(note) = &contact.note {
+        push_property(&mut vcard, &format!("NOTE:{}", escape_text(note)));
+    }
+    push_property(&mut vcard, &format!("UID:{uid}"));
+    push_property(&mut vcard, &format!("REV:{}", Utc::now().format("%Y%m%dT%H%M%SZ")));
+    push_property(&mut vcard, "END:VCARD");
     vcard
 }
 
-/// Extracts VCard fields from a slice of strings and generates a VCard.
+/// Returns `None` for an empty string, `Some` otherwise.
+fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Parses a `TEL`/`MOBILE` cell entry written by [`format_phone_entry`].
+///
+/// An entry with no `|` is a bare number carrying `default_kind` (the format
+/// used before a kind other than home/cell needed representing, and still
+/// used for plain hand-entered numbers); one with a `kind|number` prefix
+/// restores that kind, falling back to [`ContactKind::Other`] for a kind that
+/// isn't `work`/`cell`/`fax`/`home`.
+fn parse_phone_entry(entry: &str, default_kind: ContactKind) -> Phone {
+    match entry.split_once('|') {
+        Some((kind, number)) => Phone {
+            number: number.to_string(),
+            kind: match kind {
+                "work" => ContactKind::Work,
+                "cell" => ContactKind::Cell,
+                "fax" => ContactKind::Fax,
+                "home" => ContactKind::Home,
+                other => ContactKind::Other(other.to_string()),
+            },
+        },
+        None => Phone { number: entry.to_string(), kind: default_kind },
+    }
+}
+
+/// Parses an `ADDRESS` cell entry written by [`format_address_entry`].
+///
+/// An entry with no `|` is a bare street address (the format used before any
+/// other `ADR` component needed representing); one with `|`-joined components
+/// restores the full [`Address`], in the same order `format_address_entry`
+/// writes them.
+fn parse_address_entry(entry: &str) -> Address {
+    let Some((po_box, rest)) = entry.split_once('|') else {
+        return Address { street: entry.to_string(), ..Address::default() };
+    };
+    let mut parts = rest.splitn(7, '|');
+    let mut next = || parts.next().unwrap_or("").to_string();
+    Address {
+        po_box: po_box.to_string(),
+        extended: next(),
+        street: next(),
+        city: next(),
+        region: next(),
+        postal_code: next(),
+        country: next(),
+        kind: match next().as_str() {
+            "work" => ContactKind::Work,
+            "cell" => ContactKind::Cell,
+            "fax" => ContactKind::Fax,
+            "home" => ContactKind::Home,
+            other if !other.is_empty() => ContactKind::Other(other.to_string()),
+            _ => ContactKind::Home,
+        },
+    }
+}
+
+/// Builds a [`Contact`] from a CSV record using a header-driven column map.
+///
+/// Each field is looked up by name via `columns` rather than by a fixed position,
+/// so the CSV file may list its columns in any order and may contain extra columns
+/// that are simply ignored. Repeated columns (e.g. `EMAIL`, `EMAIL 2`) and
+/// semicolon-delimited cells both contribute to the same `Vec` field, so no data
+/// is dropped. A record with no usable first name is rejected.
 ///
-/// This function takes a slice of `String` representing contact data and maps
-/// each element to a corresponding VCard field in the following order:
+/// # Arguments
 ///
-/// 1. First name
-/// 2. Last name
-/// 3. Home phone number
-/// 4. Mobile phone number
-/// 5. Email address
-/// 6. Note
+/// * `record` - A single CSV data row.
+/// * `columns` - The header-to-index mapping built by [`build_column_map`].
 ///
-/// If any field is missing (slice shorter than 6 elements), it will be replaced
-/// with an empty string. If the slice has more than 6 elements, extras are ignored.
+/// # Returns
+///
+/// * `Ok(Contact)` - The contact extracted from `record`.
+/// * `Err(io::Error)` - If `record` has no usable first name.
+fn extract_contact(record: &StringRecord, columns: &ColumnMap) -> io::Result<Contact> {
+    let first = columns.get(record, "first_name");
+    if first.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid input, needs at least one field for first name"));
+    }
+    let last = columns.get(record, "last_name");
+
+    let mut phones: Vec<Phone> = columns.get_all(record, "tel").into_iter().map(|entry| parse_phone_entry(&entry, ContactKind::Home)).collect();
+    phones.extend(columns.get_all(record, "mobile").into_iter().map(|entry| parse_phone_entry(&entry, ContactKind::Cell)));
+
+    let emails = columns.get_all(record, "email").into_iter().map(|address| Email { address, kind: ContactKind::Home }).collect();
+
+    let addresses = columns.get_all(record, "address").into_iter().map(|entry| parse_address_entry(&entry)).collect();
+
+    let categories = columns.get_all(record, "categories");
+
+    Ok(Contact {
+        name: Name { first: first.to_string(), last: last.to_string() },
+        phones,
+        emails,
+        addresses,
+        org: non_empty(columns.get(record, "org")),
+        title: non_empty(columns.get(record, "title")),
+        bday: non_empty(columns.get(record, "bday")),
+        url: non_empty(columns.get(record, "url")),
+        categories,
+        note: non_empty(columns.get(record, "note")),
+        id: non_empty(columns.get(record, "id")),
+    })
+}
+
+/// Extracts a [`Contact`] from a CSV record and generates its vCard.
 ///
 /// # Arguments
 ///
-/// * `vcard_data` - A slice of `String` containing the contact information.
+/// * `record` - A single CSV data row.
+/// * `columns` - The header-to-index mapping built by [`build_column_map`].
+/// * `version` - The vCard revision to emit.
 ///
 /// # Returns
 ///
-/// * `String` - A properly formatted VCard (version 2.1) as a `String`.
+/// * `(Uuid, String)` - The contact's `UID` (also usable as a vdir filename
+///   stem, see [`process_csv_with_progress`]) alongside its formatted vCard.
 ///
 /// # Panics
 ///
@@ -105,43 +511,16 @@ fn make_vcard(first_name: &str, last_name: &str, tel: &str, mobile: &str, email:
 /// # Examples
 ///
 /// ```rust
-/// let data = vec![
-///     "Alice".to_string(),
-///     "Smith".to_string(),
-///     "123-456-7890".to_string(),
-///     "098-765-4321".to_string(),
-///     "alice@example.com".to_string(),
-///     "Friend from school".to_string()
-/// ];
-/// let vcard = extract_vcard_data(&data);
-/// println!("{}", vcard);
+/// let mut reader = csv::Reader::from_reader("FIRSTNAME,LASTNAME\nAlice,Smith\n".as_bytes());
+/// let columns = build_column_map(reader.headers().unwrap());
+/// let record = reader.records().next().unwrap().unwrap();
+/// let (uid, vcard) = extract_vcard_data(&record, &columns, VCardVersion::V2_1).unwrap();
+/// println!("{uid}\n{vcard}");
 /// ```
-///
-/// The output will be similar to:
-///
-/// ```text
-/// BEGIN:VCARD
-/// VERSION:2.1
-/// N:Smith;Alice
-/// FN:Alice Smith
-/// EMAIL;PREF;INTERNET:alice@example.com
-/// TEL;HOME;VOICE:123-456-7890
-/// TEL;HOME;VOICE:098-765-4321
-/// NOTE:Friend from school
-/// REV:1
-/// END:VCARD
-/// ```
-fn extract_vcard_data(vcard_data: &[String]) -> io::Result<String> {
-    println!("{:?}", vcard_data);
-    let first  = vcard_data.get(0).map(|s| s.as_str()).ok_or_else(
-        || {println!("hello"); io::Error::new(io::ErrorKind::InvalidInput, "Invalid input, needs at least one field for first name")}
-    )?;
-    let last   = vcard_data.get(1).map(|s| s.as_str()).unwrap_or("");
-    let tel    = vcard_data.get(2).map(|s| s.as_str()).unwrap_or("");
-    let mobile = vcard_data.get(3).map(|s| s.as_str()).unwrap_or("");
-    let email  = vcard_data.get(4).map(|s| s.as_str()).unwrap_or("");
-    let note   = vcard_data.get(5).map(|s| s.as_str()).unwrap_or("");
-    Ok(make_vcard(first, last, tel, mobile, email, note))
+fn extract_vcard_data(record: &StringRecord, columns: &ColumnMap, version: VCardVersion) -> io::Result<(Uuid, String)> {
+    let contact = extract_contact(record, columns)?;
+    let uid = resolve_uid(contact.id.as_deref());
+    Ok((uid, make_vcard(&contact, version, uid)))
 }
 
 /// Writes the given data into a file.
@@ -181,10 +560,14 @@ fn write_file<P: AsRef<Path>>(filename: P, data: &str) -> io::Result<()> {
     Ok(())
 }
 
-/// Reads a CSV file and returns all lines as `Vec<Vec<String>>`.
+/// Reads a CSV file and returns its header-derived column map together with all
+/// data records.
 ///
-/// Each line is split by the comma delimiter (`,`).
-/// The first line (header) is skipped. This function reads the entire file into memory.
+/// Parsing is delegated to the `csv` crate, so quoted fields may contain commas,
+/// embedded newlines, and escaped quotes, all of which are legal CSV but would
+/// corrupt a naive comma split. The column map is built from the header row via
+/// [`build_column_map`], so fields are later looked up by name instead of by a
+/// fixed position.
 ///
 /// # Arguments
 ///
@@ -192,8 +575,8 @@ fn write_file<P: AsRef<Path>>(filename: P, data: &str) -> io::Result<()> {
 ///
 /// # Returns
 ///
-/// * `Ok(Vec<Vec<String>>)` - Each line is represented as a `Vec<String>` containing its columns.
-/// * `Err(io::Error)` - If the file cannot be opened or read.
+/// * `Ok((ColumnMap, Vec<StringRecord>))` - The column map and the parsed data records.
+/// * `Err(io::Error)` - If the file cannot be opened, or a record fails to parse.
 ///
 /// # Panics
 ///
@@ -202,9 +585,9 @@ fn write_file<P: AsRef<Path>>(filename: P, data: &str) -> io::Result<()> {
 /// # Examples
 ///
 /// ```rust
-/// let records = read_csv_lines("data.csv").unwrap();
-/// for line in records {
-///     println!("{line:?}");
+/// let (columns, records) = read_csv_records("data.csv").unwrap();
+/// for record in &records {
+///     println!("{record:?}");
 /// }
 /// ```
 ///
@@ -216,33 +599,23 @@ fn write_file<P: AsRef<Path>>(filename: P, data: &str) -> io::Result<()> {
 /// Bob,Brown,0112233445,0611223344
 /// ```
 ///
-/// The returned value will be:
-/// ```text
-/// [
-///     ["John", "Smith", "", "0612345678", "john.smith@example.com", "Friend from work"],
-///     ["Jane", "Doe", "0987654321", "", "jane.doe@example.com", "Colleague"],
-///     ["Bob", "Brown", "0112233445", "0611223344"],
-/// ]
-/// ```
-fn read_csv_lines<P: AsRef<Path>>(filename: P) -> io::Result<Vec<Vec<String>>> {
-    let mut file = File::open(filename)?;
-    let mut contents = String::new();
-    let mut records = Vec::new();
+/// the header row maps `FIRSTNAME`, `LASTNAME`, `TEL`, `MOBILE`, `EMAIL`, and `NOTE`
+/// to their respective columns, and three records are returned.
+fn read_csv_records<P: AsRef<Path>>(filename: P) -> io::Result<(ColumnMap, Vec<StringRecord>)> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(filename)?;
+    let columns = build_column_map(reader.headers().map_err(to_io_error)?);
 
-    file.read_to_string(&mut contents)?;
-    for (i, line) in contents.lines().enumerate() {
-        if i == 0 {
-            continue;
-        }
-        let split_values: Vec<String> = line.split(',').map(|data| data.to_string()).collect();
-        // Skip empty lines
-        if split_values.len() == 1 && split_values[0].is_empty() {
-            continue;
-        }
-        records.push(split_values);
+    let mut records = Vec::new();
+    for result in reader.records() {
+        records.push(result.map_err(to_io_error)?);
     }
 
-    Ok(records)
+    Ok((columns, records))
+}
+
+/// Converts a `csv::Error` into an `io::Error`, preserving its message.
+fn to_io_error(error: csv::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
 }
 
 
@@ -293,80 +666,309 @@ fn build_output_path<P: AsRef<Path>>(input_path: P, output_extension: &str) -> i
     Ok(vcf_filename)
 }
 
-/// Processes a CSV file and generates a VCard (`.vcf`) file.
-///
-/// This function reads a CSV file containing contact information,
-/// converts each row into a VCard (version 2.1), and writes all generated
-/// VCards into a single output file with the same name as the input CSV
-/// but with a `.vcf` extension.
-///
-/// The CSV file is expected to contain the following columns in order:
-///
-/// 1. First name
-/// 2. Last name
-/// 3. Phone number
-/// 4. Mobile phone number
-/// 5. Email address
-/// 6. Note (optional)
-///
-/// Extra columns are ignored. Missing columns are replaced with empty values.
+/// Like [`build_output_path`], but builds a directory path (no extension) for
+/// a vdir: `"contacts/my_contacts.csv"` becomes `"contacts/my_contacts"`.
+fn build_output_dir<P: AsRef<Path>>(input_path: P) -> io::Result<PathBuf> {
+    let input_filename = input_path.as_ref();
+    let input_parent = input_filename.parent().unwrap_or_else(|| Path::new("."));
+    let dir_name = input_filename.file_stem().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Input path has no file name"))?;
+
+    let mut output_dir = PathBuf::from(input_parent);
+    output_dir.push(dir_name);
+    Ok(output_dir)
+}
+
+/// Outcome of converting a batch of rows/contacts, reported instead of a bare
+/// success/failure so the UI can show exactly which rows failed and why
+/// rather than collapsing every problem into one generic message.
+struct ProcessingSummary {
+    total: usize,
+    converted: usize,
+    errors: Vec<String>,
+}
+
+/// Reads a CSV file containing contact information, converts each row into a
+/// [`Contact`] and then a vCard in the requested revision, and writes the
+/// result according to `mode` (a single bundled `.vcf` file or a vdir of one
+/// `<uid>.vcf` per contact). Calls `on_progress(done, total)` after every row
+/// and returns a [`ProcessingSummary`] recording any per-row failures instead
+/// of aborting the whole conversion on the first bad row.
+///
+/// The CSV file's header row is used to locate each field by name (see
+/// [`FIELD_ALIASES`]), so columns may appear in any order. Repeated columns
+/// (`EMAIL`, `EMAIL 2`, ...) or semicolon-delimited cells contribute multiple
+/// phones/emails/addresses/categories to the same contact; first name is the
+/// only required field.
 ///
 /// # Arguments
 ///
 /// * `csv_filename` - Path to the input CSV file. Can be a relative or absolute path.
+/// * `version` - The vCard revision to emit.
+/// * `mode` - Whether to write a single bundled `.vcf` file or a vdir.
+/// * `on_progress` - Called with `(done, total)` after every row.
 ///
 /// # Returns
 ///
-/// * `Ok(())` - If the CSV file was successfully processed and the VCard file written.
-/// * `Err(io::Error)` - If any error occurs while reading the CSV file,
-///   building the output path, generating VCards, or writing the output file.
+/// * `Ok(ProcessingSummary)` - If the CSV file was read and the output written,
+///   even if some rows individually failed to convert.
+/// * `Err(io::Error)` - If the CSV file cannot be opened, the output path
+///   cannot be constructed, or the output cannot be written.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 ///
 /// * The CSV file cannot be opened or read.
-/// * The output file path cannot be constructed.
-/// * A CSV row contains invalid data.
-/// * The output file cannot be written.
+/// * The output file/directory path cannot be constructed.
+/// * The output cannot be written.
 ///
 /// Errors are propagated using the `?` operator.
+fn process_csv_with_progress(
+    csv_filename: &str,
+    version: VCardVersion,
+    mode: OutputMode,
+    mut on_progress: impl FnMut(usize, usize),
+) -> io::Result<ProcessingSummary> {
+    let (columns, records) = read_csv_records(csv_filename)?;
+    let total = records.len();
+    let mut cards = Vec::with_capacity(total);
+    let mut errors = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        match extract_vcard_data(record, &columns, version) {
+            Ok(card) => cards.push(card),
+            Err(error) => errors.push(format!("row {}: {error}", index + 1)),
+        }
+        on_progress(index + 1, total);
+    }
+    match mode {
+        OutputMode::SingleFile => {
+            let vcf_filename = build_output_path(csv_filename, "vcf")?;
+            let bundle = cards.iter().map(|(_, vcard)| vcard.as_str()).collect::<Vec<_>>().join("\r\n");
+            write_file(&vcf_filename, &bundle)?;
+        }
+        OutputMode::Vdir => {
+            let output_dir = build_output_dir(csv_filename)?;
+            fs::create_dir_all(&output_dir)?;
+            for (uid, vcard) in &cards {
+                write_file(output_dir.join(format!("{uid}.vcf")), vcard)?;
+            }
+        }
+    }
+    Ok(ProcessingSummary { total, converted: cards.len(), errors })
+}
+
+/// Column order used when writing a [`Contact`] back out as a CSV row; mirrors
+/// the canonical field names in [`FIELD_ALIASES`]. The `ID` column round-trips
+/// a contact's vCard `UID` so re-exporting a previously parsed `.vcf` file
+/// keeps the same UID (see [`resolve_uid`]).
+const CSV_HEADER: [&str; 13] =
+    ["FIRSTNAME", "LASTNAME", "TEL", "MOBILE", "EMAIL", "ADDRESS", "ORG", "TITLE", "BDAY", "URL", "CATEGORIES", "NOTE", "ID"];
+
+/// Formats a [`Phone`] as a `TEL`/`MOBILE` cell entry, parsed back by
+/// [`parse_phone_entry`] with the same `default_kind`. A phone whose kind
+/// already matches the column's default (home for `TEL`, cell for `MOBILE`)
+/// is written bare, for backward compatibility with plain hand-entered
+/// numbers; any other kind (work, fax, or a custom [`ContactKind::Other`]) is
+/// prefixed as `kind|number` so it survives a CSV round trip instead of being
+/// collapsed to the column's default on import.
+fn format_phone_entry(phone: &Phone, default_kind: ContactKind) -> String {
+    if phone.kind == default_kind {
+        phone.number.clone()
+    } else {
+        format!("{}|{}", phone.kind.as_type_value(), phone.number)
+    }
+}
+
+/// Formats an [`Address`] as an `ADDRESS` cell entry, parsed back by
+/// [`parse_address_entry`]. An address with nothing but a street and a home
+/// kind is written as a bare street, matching the pre-existing plain format;
+/// one with any other component set is written as all seven `ADR` components
+/// plus its kind, `|`-joined, so no data is lost on re-import.
+fn format_address_entry(address: &Address) -> String {
+    let is_plain_street =
+        address.kind == ContactKind::Home && [&address.po_box, &address.extended, &address.city, &address.region, &address.postal_code, &address.country].iter().all(|component| component.is_empty());
+    if is_plain_street {
+        return address.street.clone();
+    }
+    [
+        address.po_box.as_str(),
+        address.extended.as_str(),
+        address.street.as_str(),
+        address.city.as_str(),
+        address.region.as_str(),
+        address.postal_code.as_str(),
+        address.country.as_str(),
+        address.kind.as_type_value(),
+    ]
+    .join("|")
+}
+
+/// Flattens a [`Contact`] into a single CSV row matching [`CSV_HEADER`].
+///
+/// Repeated fields (emails, categories) are joined back into one cell with
+/// `;`, the same delimiter [`ColumnMap::get_all`] splits on. Phones are split
+/// the same way between the `TEL` and `MOBILE` cells as on import: every
+/// cell-kind phone goes to `MOBILE`, every other phone to `TEL` (see
+/// [`format_phone_entry`] for how a non-home kind survives the round trip).
+/// Addresses are joined into the `ADDRESS` cell the same way (see
+/// [`format_address_entry`]).
+fn contact_to_csv_row(contact: &Contact) -> [String; 13] {
+    let tel = contact.phones.iter().filter(|phone| phone.kind != ContactKind::Cell).map(|phone| format_phone_entry(phone, ContactKind::Home)).collect::<Vec<_>>().join(";");
+    let mobile = contact.phones.iter().filter(|phone| phone.kind == ContactKind::Cell).map(|phone| format_phone_entry(phone, ContactKind::Cell)).collect::<Vec<_>>().join(";");
+    let email = contact.emails.iter().map(|email| email.address.as_str()).collect::<Vec<_>>().join(";");
+    let address = contact.addresses.iter().map(format_address_entry).collect::<Vec<_>>().join(";");
+    let categories = contact.categories.join(";");
+    [
+        contact.name.first.clone(),
+        contact.name.last.clone(),
+        tel,
+        mobile,
+        email,
+        address,
+        contact.org.clone().unwrap_or_default(),
+        contact.title.clone().unwrap_or_default(),
+        contact.bday.clone().unwrap_or_default(),
+        contact.url.clone().unwrap_or_default(),
+        categories,
+        contact.note.clone().unwrap_or_default(),
+        contact.id.clone().unwrap_or_default(),
+    ]
+}
+
+/// Processes a `.vcf` file and generates a CSV (`.csv`) file.
 ///
-/// # Examples
+/// This is the reverse of [`process_csv_with_progress`]: it parses the vCards
+/// in `vcf_filename` into [`Contact`]s via [`vcard_parser::parse_vcards`] and
+/// writes one CSV row per contact (see [`contact_to_csv_row`]), so a
+/// previously exported or hand-edited `.vcf` file can be brought back into
+/// spreadsheet form. Calls `on_progress(done, total)` after every contact and
+/// returns a [`ProcessingSummary`].
 ///
-/// ```no_run
-/// use std::io;
+/// # Arguments
 ///
-/// fn main() -> io::Result<()> {
-///     process_csv("contacts.csv")?;
-///     Ok(())
-/// }
-/// ```
+/// * `vcf_filename` - Path to the input `.vcf` file. Can be a relative or absolute path.
+/// * `on_progress` - Called with `(done, total)` after every contact.
+///
+/// # Returns
 ///
-/// If `contacts.csv` exists, this will generate a `contacts.vcf` file
-/// in the same directory.
-fn process_csv(csv_filename: &str) -> io::Result<()> {
-    let lines = read_csv_lines(csv_filename)?;
-    let vcf_filename = build_output_path(csv_filename, "vcf")?;
-    let all_vcard: Vec<String> = lines.iter().map(|element| extract_vcard_data(element)).collect::<io::Result<Vec<String>>>()?;
-    write_file(&vcf_filename, &all_vcard.join("\n"))
+/// * `Ok(ProcessingSummary)` - If the `.vcf` file was successfully processed and the CSV file written.
+/// * `Err(io::Error)` - If any error occurs while reading, parsing, or writing.
+fn process_vcf_with_progress(vcf_filename: &str, mut on_progress: impl FnMut(usize, usize)) -> io::Result<ProcessingSummary> {
+    let mut input = String::new();
+    File::open(vcf_filename)?.read_to_string(&mut input)?;
+    let contacts = vcard_parser::parse_vcards(&input)?;
+    let total = contacts.len();
+
+    let csv_filename = build_output_path(vcf_filename, "csv")?;
+    let mut writer = csv::WriterBuilder::new().from_path(&csv_filename).map_err(to_io_error)?;
+    writer.write_record(CSV_HEADER).map_err(to_io_error)?;
+    for (index, contact) in contacts.iter().enumerate() {
+        writer.write_record(contact_to_csv_row(contact)).map_err(to_io_error)?;
+        on_progress(index + 1, total);
+    }
+    writer.flush()?;
+    Ok(ProcessingSummary { total, converted: total, errors: Vec::new() })
+}
+
+/// Progress and outcome messages sent from a background conversion thread to
+/// the UI thread, following the meli `Async`/`AsyncStatus` pattern.
+enum WorkerMessage {
+    /// `done` out of `total` rows/contacts have been converted so far.
+    Progress { done: usize, total: usize },
+    /// The conversion finished, successfully or not.
+    Done(io::Result<ProcessingSummary>),
+}
+
+/// The UI's view of a (possibly backgrounded) conversion.
+#[derive(Default)]
+enum ProcessingState {
+    #[default]
+    Idle,
+    Running {
+        done: usize,
+        total: usize,
+    },
+    Finished(io::Result<ProcessingSummary>),
 }
 
 /// Application state for the VCard generator UI.
 ///
-/// This struct holds the runtime state of the application,
-/// including the currently selected CSV file. It is used by
-/// the egui/eframe application to drive the user interface and
-/// trigger CSV-to-VCard processing.
+/// This struct holds the runtime state of the application: the output vCard
+/// version, the state of the in-flight or last-run conversion, and the
+/// channel the background worker thread reports progress on.
 ///
 /// # Fields
 ///
-/// * `selected_file` - Path to the selected CSV file.
-///   - `Some(String)` when the user has chosen a file.
-///   - `None` when no file is selected yet.
+/// * `version` - The vCard revision to write, chosen from the version dropdown.
+/// * `mode` - Whether a CSV conversion writes a single bundled `.vcf` file or
+///   a vdir, chosen from the output mode dropdown.
+/// * `state` - The current or last conversion's progress/outcome.
+/// * `receiver` - Receives [`WorkerMessage`]s from the background thread while a
+///   conversion is running; `None` when idle.
 #[derive(Default)]
 struct VCardGenerator {
-    selected_file: Option<String>
+    version: VCardVersion,
+    mode: OutputMode,
+    state: ProcessingState,
+    receiver: Option<mpsc::Receiver<WorkerMessage>>,
+}
+
+impl VCardGenerator {
+    /// Spawns a background thread that converts `csv_filename` to vCards (a
+    /// single `.vcf` file or a vdir, per `self.mode`), reporting progress and the final [`ProcessingSummary`] over a
+    /// channel so the UI thread is never blocked on the conversion.
+    fn spawn_csv_worker(&mut self, csv_filename: String, ctx: Context) {
+        let (sender, receiver) = mpsc::channel();
+        self.receiver = Some(receiver);
+        self.state = ProcessingState::Running { done: 0, total: 0 };
+        let version = self.version;
+        let mode = self.mode;
+        thread::spawn(move || {
+            let result = process_csv_with_progress(&csv_filename, version, mode, |done, total| {
+                let _ = sender.send(WorkerMessage::Progress { done, total });
+                ctx.request_repaint();
+            });
+            let _ = sender.send(WorkerMessage::Done(result));
+            ctx.request_repaint();
+        });
+    }
+
+    /// Spawns a background thread that converts `vcf_filename` back to a CSV
+    /// file, mirroring [`VCardGenerator::spawn_csv_worker`].
+    fn spawn_vcf_worker(&mut self, vcf_filename: String, ctx: Context) {
+        let (sender, receiver) = mpsc::channel();
+        self.receiver = Some(receiver);
+        self.state = ProcessingState::Running { done: 0, total: 0 };
+        thread::spawn(move || {
+            let result = process_vcf_with_progress(&vcf_filename, |done, total| {
+                let _ = sender.send(WorkerMessage::Progress { done, total });
+                ctx.request_repaint();
+            });
+            let _ = sender.send(WorkerMessage::Done(result));
+            ctx.request_repaint();
+        });
+    }
+
+    /// Drains every [`WorkerMessage`] currently queued on `receiver`, updating
+    /// `state` accordingly.
+    fn poll_worker(&mut self) {
+        let Some(receiver) = self.receiver.take() else {
+            return;
+        };
+        let mut still_running = true;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                WorkerMessage::Progress { done, total } => self.state = ProcessingState::Running { done, total },
+                WorkerMessage::Done(result) => {
+                    self.state = ProcessingState::Finished(result);
+                    still_running = false;
+                }
+            }
+        }
+        if still_running {
+            self.receiver = Some(receiver);
+        }
+    }
 }
 
 /// Implements the egui application logic for the VCard generator.
@@ -374,46 +976,79 @@ struct VCardGenerator {
 /// This implementation defines the user interface and behavior of the
 /// application. It allows the user to:
 ///
-/// - Open a file dialog restricted to CSV files
-/// - Select a CSV file containing contact data
-/// - Automatically process the selected file and generate a VCard file
+/// - Open a file dialog restricted to CSV files and convert it to a `.vcf` file
+///   or a vdir, as chosen in the output mode dropdown
+/// - Open a file dialog restricted to `.vcf` files and convert it back to a CSV file
 ///
 /// The UI is rendered using `egui`, and the application state is stored
 /// in the `VCardGenerator` struct.
 ///
 /// # Behavior
 ///
-/// - When the "Open CSV file" button is clicked, a file picker dialog opens.
-/// - Once a file is selected, the CSV file is immediately processed.
-/// - If processing succeeds, a success message is displayed.
-/// - If processing fails, an error message is displayed.
-/// - After processing, the selected file state is reset.
-///
-/// # Notes
-///
-/// - The CSV processing is triggered inside the UI update loop.
-/// - For large files or long-running operations, this logic should be
-///   moved to a background thread to avoid blocking the UI.
+/// - Clicking "Open CSV file"/"Open VCF file" opens a file picker, then spawns
+///   a background thread (see [`VCardGenerator::spawn_csv_worker`] /
+///   [`VCardGenerator::spawn_vcf_worker`]) so conversion never blocks the UI.
+/// - While a conversion runs, both buttons are disabled and a progress bar
+///   tracks the rows/contacts processed so far.
+/// - Once finished, a summary is shown, including any per-row errors instead
+///   of a single generic failure message.
 ///
 /// # See Also
 ///
-/// - [`process_csv`] — Handles CSV-to-VCard conversion.
+/// - [`process_csv_with_progress`] — Handles CSV-to-VCard conversion.
+/// - [`process_vcf_with_progress`] — Handles VCard-to-CSV conversion.
 /// - [`VCardGenerator`] — Stores the application state.
 impl eframe::App for VCardGenerator {
     fn update(&mut self, ctx: &Context, _: &mut Frame) {
+        self.poll_worker();
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
-                if ui.add_sized([200.0, 40.0], egui::Button::new("Open CSV file")).clicked() {
-                    if let Some(path) = rfd::FileDialog::new().add_filter("CSV files", &["csv"]).pick_file() {
-                        self.selected_file = Some(path.display().to_string());
+                egui::ComboBox::from_label("VCard version")
+                    .selected_text(self.version.as_str())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.version, VCardVersion::V2_1, "2.1");
+                        ui.selectable_value(&mut self.version, VCardVersion::V3_0, "3.0");
+                        ui.selectable_value(&mut self.version, VCardVersion::V4_0, "4.0");
+                    });
+
+                egui::ComboBox::from_label("CSV output")
+                    .selected_text(self.mode.as_str())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.mode, OutputMode::SingleFile, OutputMode::SingleFile.as_str());
+                        ui.selectable_value(&mut self.mode, OutputMode::Vdir, OutputMode::Vdir.as_str());
+                    });
+
+                let busy = matches!(self.state, ProcessingState::Running { .. });
+                ui.add_enabled_ui(!busy, |ui| {
+                    if ui.add_sized([200.0, 40.0], egui::Button::new("Open CSV file")).clicked() {
+                        if let Some(path) = rfd::FileDialog::new().add_filter("CSV files", &["csv"]).pick_file() {
+                            self.spawn_csv_worker(path.display().to_string(), ctx.clone());
+                        }
+                    }
+                    if ui.add_sized([200.0, 40.0], egui::Button::new("Open VCF file")).clicked() {
+                        if let Some(path) = rfd::FileDialog::new().add_filter("VCF files", &["vcf"]).pick_file() {
+                            self.spawn_vcf_worker(path.display().to_string(), ctx.clone());
+                        }
+                    }
+                });
+
+                match &self.state {
+                    ProcessingState::Idle => {}
+                    ProcessingState::Running { done, total } => {
+                        let fraction = if *total == 0 { 0.0 } else { *done as f32 / *total as f32 };
+                        ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                        ctx.request_repaint();
+                    }
+                    ProcessingState::Finished(Ok(summary)) => {
+                        ui.label(format!("Converted {}/{} contact(s)", summary.converted, summary.total));
+                        for error in &summary.errors {
+                            ui.colored_label(egui::Color32::RED, error);
+                        }
+                    }
+                    ProcessingState::Finished(Err(error)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Invalid input file: {error}"));
                     }
-                }
-                if let Some(file) = &self.selected_file {
-                    match process_csv(file) {
-                        Ok(_) =>ui.label("Done !".to_string()),
-                        Err(_) => ui.label("Invalid input file".to_string())
-                    };
-                    self.selected_file = None;
                 }
             });
         });
@@ -427,7 +1062,7 @@ impl eframe::App for VCardGenerator {
 ///
 /// # Window Configuration
 ///
-/// - Initial size: 300x100 pixels
+/// - Initial size: 300x200 pixels
 /// - Non-resizable
 /// - Maximize button disabled
 ///
@@ -450,8 +1085,149 @@ impl eframe::App for VCardGenerator {
 fn main() -> eframe::Result {
     env_logger::init();
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([300.0, 100.0]).with_resizable(false).with_maximize_button(false),
+        viewport: egui::ViewportBuilder::default().with_inner_size([300.0, 200.0]).with_resizable(false).with_maximize_button(false),
         ..Default::default()
     };
     eframe::run_native("VCard Generator", options, Box::new(|_| { Ok(Box::<VCardGenerator>::default()) }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_line_does_not_fold_at_the_75_octet_boundary() {
+        let line = "a".repeat(75);
+        assert_eq!(fold_line(&line), line);
+    }
+
+    #[test]
+    fn fold_line_folds_one_octet_past_the_boundary() {
+        let line = "a".repeat(76);
+        let folded = fold_line(&line);
+        assert_eq!(folded, format!("{}\r\n a", "a".repeat(75)));
+    }
+
+    #[test]
+    fn column_map_matches_header_aliases_regardless_of_spacing_and_case() {
+        let headers = StringRecord::from(vec!["First Name", "SURNAME", "MOBILE PHONE", "unrelated"]);
+        let columns = build_column_map(&headers);
+        let record = StringRecord::from(vec!["Alice", "Smith", "0612345678", "ignored"]);
+
+        assert_eq!(columns.get(&record, "first_name"), "Alice");
+        assert_eq!(columns.get(&record, "last_name"), "Smith");
+        assert_eq!(columns.get(&record, "mobile"), "0612345678");
+        assert_eq!(columns.get(&record, "org"), "");
+    }
+
+    #[test]
+    fn extract_contact_maps_semicolon_delimited_cells_onto_the_contact_model() {
+        let headers = StringRecord::from(vec!["FIRSTNAME", "LASTNAME", "EMAIL"]);
+        let columns = build_column_map(&headers);
+        let record = StringRecord::from(vec!["Alice", "Smith", "alice@work.example;alice@home.example"]);
+
+        let contact = extract_contact(&record, &columns).unwrap();
+        let addresses: Vec<&str> = contact.emails.iter().map(|email| email.address.as_str()).collect();
+        assert_eq!(addresses, vec!["alice@work.example", "alice@home.example"]);
+    }
+
+    #[test]
+    fn resolve_uid_is_stable_for_a_uuid_or_an_external_id_but_random_otherwise() {
+        let existing_uuid = "2362c8ee-6b01-4a2c-9c2c-1f4f1a8f0b8e";
+        assert_eq!(resolve_uid(Some(existing_uuid)), Uuid::parse_str(existing_uuid).unwrap());
+
+        let from_external_id = resolve_uid(Some("row-42"));
+        assert_eq!(from_external_id, resolve_uid(Some("row-42")));
+
+        assert_ne!(resolve_uid(None), resolve_uid(None));
+    }
+
+    #[test]
+    fn csv_row_round_trip_preserves_every_phone_kind_and_full_address() {
+        let contact = Contact {
+            name: Name { first: "Alice".to_string(), last: "Smith".to_string() },
+            phones: vec![
+                Phone { number: "0600000000".to_string(), kind: ContactKind::Home },
+                Phone { number: "0611111111".to_string(), kind: ContactKind::Work },
+                Phone { number: "0622222222".to_string(), kind: ContactKind::Fax },
+                Phone { number: "0633333333".to_string(), kind: ContactKind::Cell },
+            ],
+            addresses: vec![Address {
+                po_box: "PO 5".to_string(),
+                extended: "Suite 2".to_string(),
+                street: "1 Main St".to_string(),
+                city: "Springfield".to_string(),
+                region: "IL".to_string(),
+                postal_code: "62701".to_string(),
+                country: "US".to_string(),
+                kind: ContactKind::Work,
+            }],
+            ..Contact::default()
+        };
+
+        let row = contact_to_csv_row(&contact);
+        let headers = StringRecord::from(CSV_HEADER.to_vec());
+        let columns = build_column_map(&headers);
+        let record = StringRecord::from(row.to_vec());
+        let round_tripped = extract_contact(&record, &columns).unwrap();
+
+        let mut kinds: Vec<ContactKind> = round_tripped.phones.iter().map(|phone| phone.kind.clone()).collect();
+        kinds.sort_by_key(|kind| kind.as_type_value().to_string());
+        assert_eq!(kinds, vec![ContactKind::Cell, ContactKind::Fax, ContactKind::Home, ContactKind::Work]);
+
+        let address = &round_tripped.addresses[0];
+        assert_eq!(address.po_box, "PO 5");
+        assert_eq!(address.extended, "Suite 2");
+        assert_eq!(address.street, "1 Main St");
+        assert_eq!(address.city, "Springfield");
+        assert_eq!(address.region, "IL");
+        assert_eq!(address.postal_code, "62701");
+        assert_eq!(address.country, "US");
+        assert_eq!(address.kind, ContactKind::Work);
+    }
+
+    #[test]
+    fn process_csv_with_progress_writes_a_vdir_with_one_file_per_contact() {
+        let dir = std::env::temp_dir().join(format!("vcard_generator_vdir_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let csv_path = dir.join("contacts.csv");
+        std::fs::write(&csv_path, "FIRSTNAME,LASTNAME\nAlice,Smith\nBob,Jones\n").unwrap();
+
+        let summary = process_csv_with_progress(csv_path.to_str().unwrap(), VCardVersion::V3_0, OutputMode::Vdir, |_, _| {}).unwrap();
+        assert_eq!(summary.converted, 2);
+
+        let vdir = dir.join("contacts");
+        let mut files: Vec<String> = std::fs::read_dir(&vdir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|name| name.ends_with(".vcf")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn make_vcard_and_parse_vcards_round_trip_every_phone_kind_and_version() {
+        // Each version's own writer (see `push_phones`) encodes a phone kind
+        // differently (e.g. 2.1 writes `Fax` as `TEL;HOME;FAX`, folding it
+        // under the `HOME` flag), so this exercises the actual wire format
+        // rather than just the CSV cell format covered elsewhere.
+        let versions = [VCardVersion::V2_1, VCardVersion::V3_0, VCardVersion::V4_0];
+        let kinds = [ContactKind::Home, ContactKind::Work, ContactKind::Cell, ContactKind::Fax, ContactKind::Other("pager".to_string())];
+
+        for version in versions {
+            for kind in &kinds {
+                let contact = Contact {
+                    name: Name { first: "Alice".to_string(), last: "Smith".to_string() },
+                    phones: vec![Phone { number: "0600000000".to_string(), kind: kind.clone() }],
+                    ..Contact::default()
+                };
+
+                let vcard = make_vcard(&contact, version, resolve_uid(None));
+                let parsed = vcard_parser::parse_vcards(&vcard).unwrap();
+
+                assert_eq!(parsed.len(), 1, "version {version:?}, kind {kind:?}");
+                assert_eq!(parsed[0].phones[0].kind, *kind, "phone kind lost for version {version:?}, kind {kind:?}");
+            }
+        }
+    }
+}
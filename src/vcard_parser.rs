@@ -0,0 +1,329 @@
+//! A parser for `.vcf` vCard files, producing [`Contact`]s.
+//!
+//! Implements the RFC 6350 unfolding and unescaping rules needed to round-trip
+//! files written by `make_vcard` (or by any other compliant vCard writer): a
+//! CRLF (or bare LF) followed by a space or tab continues the previous logical
+//! line, and `\n`, `\,`, `\;`, `\\` are unescaped back to their literal
+//! characters. A stream may contain multiple `BEGIN:VCARD`/`END:VCARD` blocks,
+//! each producing one [`Contact`].
+
+use std::io;
+
+use crate::contact::{Address, Contact, ContactKind, Email, Phone};
+
+/// Unfolds a vCard's physical lines into logical lines per RFC 6350 §3.2: a
+/// line break followed by a single space or tab continues the previous line,
+/// and the break plus the leading whitespace are removed.
+fn unfold(input: &str) -> String {
+    let mut unfolded = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\r' && chars.peek() == Some(&'\n') {
+            chars.next();
+            if matches!(chars.peek(), Some(' ') | Some('\t')) {
+                chars.next();
+                continue;
+            }
+            unfolded.push('\n');
+        } else if ch == '\n' {
+            if matches!(chars.peek(), Some(' ') | Some('\t')) {
+                chars.next();
+                continue;
+            }
+            unfolded.push('\n');
+        } else {
+            unfolded.push(ch);
+        }
+    }
+    unfolded
+}
+
+/// Reverses the text escaping from RFC 6350 §3.4: `\n`/`\N` → newline,
+/// `\,` → `,`, `\;` → `;`, `\\` → `\`.
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+/// A single unfolded `NAME;PARAM;PARAM=...:VALUE` property line, with `name`
+/// upper-cased and `value` still escaped.
+struct Property {
+    name: String,
+    params: Vec<String>,
+    value: String,
+}
+
+/// Finds the byte index of the first unescaped occurrence of `needle` in `line`.
+fn find_unescaped(line: &str, needle: char) -> Option<usize> {
+    let mut escaped = false;
+    for (index, ch) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == needle {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// Splits `value` on every unescaped occurrence of `delim`, mirroring
+/// [`find_unescaped`] but returning all segments instead of just the first
+/// split point.
+///
+/// Each segment is still escaped (an escaped `delim` is left untouched rather
+/// than split on), so callers must [`unescape_text`] a segment after
+/// splitting, not before — unescaping first would turn an escaped `delim`
+/// into a literal one and have it wrongly split here too.
+fn split_unescaped(value: &str, delim: char) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, ch) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == delim {
+            segments.push(&value[start..index]);
+            start = index + ch.len_utf8();
+        }
+    }
+    segments.push(&value[start..]);
+    segments
+}
+
+/// Splits a logical vCard line into its property name, parameters, and value.
+///
+/// Everything before the first unescaped `:` is split on `;` into a name and
+/// its parameters; everything after is the (still-escaped) value.
+fn parse_property(line: &str) -> Option<Property> {
+    let colon = find_unescaped(line, ':')?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts.map(|part| part.to_string()).collect();
+    Some(Property { name, params, value: value.to_string() })
+}
+
+/// Infers a [`ContactKind`] from a property's parameters, recognizing both the
+/// vCard 2.1 bare-flag syntax (`HOME`, `WORK`, ...) and the 3.0/4.0
+/// `TYPE=value,value` syntax. An unrecognized type value (e.g. `TYPE=pager`)
+/// is kept as [`ContactKind::Other`] rather than discarded, so a round trip
+/// through this parser and back through `make_vcard` doesn't silently
+/// downgrade it to `HOME`. Defaults to [`ContactKind::Home`] when no type
+/// value at all is present.
+///
+/// Every param is scanned before deciding, and `work` > `cell` > `fax` > `home`
+/// in priority when more than one is present — this matters because the 2.1
+/// writer emits a fax number as `TEL;HOME;FAX:...`, so stopping at the first
+/// recognized flag would report it as `Home` and silently drop the `Fax`.
+fn kind_from_params(params: &[String]) -> ContactKind {
+    let (mut has_work, mut has_cell, mut has_fax, mut has_home) = (false, false, false, false);
+    let mut other = None;
+    for param in params {
+        let upper = param.to_ascii_uppercase();
+        let values: Vec<&str> = match upper.strip_prefix("TYPE=") {
+            Some(rest) => rest.split(',').collect(),
+            // Any other `NAME=value` parameter (e.g. `CHARSET=UTF-8`) is not a type.
+            None if upper.contains('=') => Vec::new(),
+            None => vec![upper.as_str()],
+        };
+        for value in values {
+            match value {
+                "WORK" => has_work = true,
+                "CELL" => has_cell = true,
+                "FAX" => has_fax = true,
+                "HOME" => has_home = true,
+                "PREF" | "VOICE" | "INTERNET" => {}
+                other_value if other.is_none() => other = Some(other_value.to_ascii_lowercase()),
+                _ => {}
+            }
+        }
+    }
+    if has_work {
+        ContactKind::Work
+    } else if has_cell {
+        ContactKind::Cell
+    } else if has_fax {
+        ContactKind::Fax
+    } else if has_home {
+        ContactKind::Home
+    } else {
+        other.map(ContactKind::Other).unwrap_or(ContactKind::Home)
+    }
+}
+
+/// Applies a parsed property to the contact currently being built.
+///
+/// A property whose value has structure of its own (`N`'s and `ADR`'s `;`-joined
+/// components, `CATEGORIES`'s `,`-joined list) is split on the still-escaped
+/// value via [`split_unescaped`] first, and each resulting component is
+/// unescaped individually — splitting the already-unescaped value instead
+/// would treat an escaped delimiter (e.g. a comma inside one category) as a
+/// real one and corrupt the split. A scalar property is simply unescaped as a
+/// whole.
+fn apply_property(contact: &mut Contact, property: &Property) {
+    match property.name.as_str() {
+        "N" => {
+            let mut components = split_unescaped(&property.value, ';').into_iter().map(unescape_text);
+            contact.name.last = components.next().unwrap_or_default();
+            contact.name.first = components.next().unwrap_or_default();
+        }
+        "FN" if contact.name.first.is_empty() && contact.name.last.is_empty() => {
+            let value = unescape_text(&property.value);
+            let mut words = value.splitn(2, ' ');
+            contact.name.first = words.next().unwrap_or("").to_string();
+            contact.name.last = words.next().unwrap_or("").to_string();
+        }
+        "TEL" => contact.phones.push(Phone { number: unescape_text(&property.value), kind: kind_from_params(&property.params) }),
+        "EMAIL" => contact.emails.push(Email { address: unescape_text(&property.value), kind: kind_from_params(&property.params) }),
+        "ADR" => {
+            let mut components = split_unescaped(&property.value, ';').into_iter().map(unescape_text);
+            contact.addresses.push(Address {
+                po_box: components.next().unwrap_or_default(),
+                extended: components.next().unwrap_or_default(),
+                street: components.next().unwrap_or_default(),
+                city: components.next().unwrap_or_default(),
+                region: components.next().unwrap_or_default(),
+                postal_code: components.next().unwrap_or_default(),
+                country: components.next().unwrap_or_default(),
+                kind: kind_from_params(&property.params),
+            });
+        }
+        "ORG" => contact.org = Some(unescape_text(&property.value)),
+        "TITLE" => contact.title = Some(unescape_text(&property.value)),
+        "BDAY" => contact.bday = Some(unescape_text(&property.value)),
+        "URL" => contact.url = Some(unescape_text(&property.value)),
+        "CATEGORIES" => {
+            contact.categories =
+                split_unescaped(&property.value, ',').into_iter().map(|category| unescape_text(category.trim())).filter(|category| !category.is_empty()).collect();
+        }
+        "NOTE" => contact.note = Some(unescape_text(&property.value)),
+        "UID" => contact.id = Some(unescape_text(&property.value)),
+        _ => {}
+    }
+}
+
+/// Parses a full `.vcf` stream into one [`Contact`] per `BEGIN:VCARD`/`END:VCARD`
+/// block.
+///
+/// # Errors
+///
+/// Returns an error if a `BEGIN:VCARD` is never closed by a matching
+/// `END:VCARD`, or vice versa.
+pub fn parse_vcards(input: &str) -> io::Result<Vec<Contact>> {
+    let unfolded = unfold(input);
+    let mut contacts = Vec::new();
+    let mut current: Option<Contact> = None;
+
+    for line in unfolded.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        if line.eq_ignore_ascii_case("BEGIN:VCARD") {
+            if current.replace(Contact::default()).is_some() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "BEGIN:VCARD without a matching END:VCARD"));
+            }
+            continue;
+        }
+        if line.eq_ignore_ascii_case("END:VCARD") {
+            let contact = current
+                .take()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "END:VCARD without a matching BEGIN:VCARD"))?;
+            contacts.push(contact);
+            continue;
+        }
+        let Some(contact) = current.as_mut() else {
+            continue;
+        };
+        if let Some(property) = parse_property(line) {
+            apply_property(contact, &property);
+        }
+    }
+
+    if current.is_some() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "BEGIN:VCARD without a matching END:VCARD"));
+    }
+
+    Ok(contacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_from_params_keeps_an_unrecognized_type_as_other() {
+        let params = vec!["TYPE=pager".to_string()];
+        assert_eq!(kind_from_params(&params), ContactKind::Other("pager".to_string()));
+    }
+
+    #[test]
+    fn kind_from_params_recognizes_known_types_over_other() {
+        let params = vec!["TYPE=pref,pager,work".to_string()];
+        assert_eq!(kind_from_params(&params), ContactKind::Work);
+    }
+
+    #[test]
+    fn parse_vcards_round_trips_a_folded_and_escaped_value() {
+        let long_prefix = "a".repeat(75);
+        let mut input = String::new();
+        input.push_str("BEGIN:VCARD\r\n");
+        input.push_str("N:Doe;Jane;;;\r\n");
+        input.push_str("NOTE:");
+        input.push_str(&long_prefix);
+        input.push_str("\r\n b"); // fold point: CRLF + one marker space, then literal "b"
+        input.push_str(r"\n\,\;\\tail"); // escaped newline, comma, semicolon, backslash
+        input.push_str("\r\nEND:VCARD\r\n");
+
+        let contacts = parse_vcards(&input).unwrap();
+        assert_eq!(contacts.len(), 1);
+
+        let mut expected = long_prefix;
+        expected.push_str("b\n,;\\tail");
+        assert_eq!(contacts[0].note.as_deref(), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn kind_from_params_prefers_fax_over_home_when_both_flags_are_present() {
+        // The app's own 2.1 writer emits a fax number as `TEL;HOME;FAX:`
+        // (see `push_phones`), so `HOME` and `FAX` appearing together must
+        // resolve to `Fax`, not fall back to the first flag seen.
+        let params = vec!["HOME".to_string(), "FAX".to_string()];
+        assert_eq!(kind_from_params(&params), ContactKind::Fax);
+    }
+
+    #[test]
+    fn apply_property_unescapes_after_splitting_so_an_escaped_delimiter_is_not_a_split_point() {
+        let mut input = String::new();
+        input.push_str("BEGIN:VCARD\r\n");
+        input.push_str("N:Doe;Jane;;;\r\n");
+        input.push_str("CATEGORIES:A");
+        input.push_str(r"\,B,C");
+        input.push_str("\r\n");
+        input.push_str("ADR:");
+        input.push_str(r";;1 Main St\; Suite 2;Springfield;;;");
+        input.push_str("\r\nEND:VCARD\r\n");
+
+        let contacts = parse_vcards(&input).unwrap();
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].categories, vec!["A,B".to_string(), "C".to_string()]);
+        assert_eq!(contacts[0].addresses[0].street, "1 Main St; Suite 2");
+        assert_eq!(contacts[0].addresses[0].city, "Springfield");
+    }
+}